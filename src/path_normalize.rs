@@ -36,11 +36,13 @@ pub fn is_reserved_path(path: &OsStr) -> bool {
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum NormalizeMode {
     /// Normalize the path only if it (or parent) exists (ie, as `fs::canonicalize()`).
     Strict,
-    /// Normalize the path only if it (or *any* parent) exists, normalizing lexically for later paths.
-    /// * note: this will check for the existence of the nearest parent path, which may be expensive
+    /// Normalize the path against the nearest existing parent, then resolve the rest lexically.
+    /// * note: this walks the path component-by-component looking for that parent, which may be expensive
     Hybrid,
     /// Normalize the path lexically, based solely on the path text, if the path (or parent) does not exist.
     #[default]
@@ -48,9 +50,49 @@ pub enum NormalizeMode {
 }
 
 #[derive(Builder, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct NormalizeOptions {
     // #[builder(default = "NormalizeMode::Lexical")]
     mode: NormalizeMode,
+    /// Expand a leading `~` component to the user's home directory before normalizing.
+    #[builder(default)]
+    expand_tilde: bool,
+}
+
+/// Expand a leading `~` component to the current user's home directory.
+///
+/// Only expands when `~` is a standalone leading component (eg, `~/foo`); otherwise (including
+/// `~user/foo`-style names, or when the home directory can't be determined) `path` is returned
+/// unchanged.
+pub fn expand_tilde<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    let mut components = path.components();
+    if let Some(Component::Normal(first)) = components.next() {
+        if first == OsStr::new("~") {
+            if let Some(mut home) = dirs::home_dir() {
+                home.push(components.as_path());
+                return home;
+            }
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Rewrite a path under the user's home directory back to a `~`-prefixed form, for display.
+///
+/// If `path` isn't under the home directory (or the home directory can't be determined), `path`
+/// is returned unchanged.
+pub fn fold_home_dir<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    if let Some(home) = dirs::home_dir() {
+        if let Ok(rest) = path.strip_prefix(&home) {
+            let mut result = PathBuf::from("~");
+            result.push(rest);
+            return result;
+        }
+    }
+    path.to_path_buf()
 }
 
 pub fn normalize_path_with_options<P: AsRef<Path> + std::fmt::Debug>(
@@ -58,6 +100,13 @@ pub fn normalize_path_with_options<P: AsRef<Path> + std::fmt::Debug>(
     options: &NormalizeOptions,
 ) -> std::io::Result<PathBuf> {
     let path = path.as_ref();
+    let expanded;
+    let path = if options.expand_tilde {
+        expanded = expand_tilde(path);
+        expanded.as_path()
+    } else {
+        path
+    };
     if is_reserved_path(&path.as_os_str()) {
         return Ok(path.to_string_lossy().to_ascii_uppercase().into());
     };
@@ -71,6 +120,13 @@ pub fn normalize_path_with_options<P: AsRef<Path> + std::fmt::Debug>(
             }
         }
     };
+    if options.mode == NormalizeMode::Hybrid {
+        return normalize_path_hybrid(path);
+    }
+    normalize_path_lexical_from_cwd(path)
+}
+
+fn normalize_path_lexical_from_cwd(path: &Path) -> std::io::Result<PathBuf> {
     let mut normalized_pathbuf = fs::canonicalize(".")?;
     for component in path.components() {
         match component {
@@ -87,6 +143,384 @@ pub fn normalize_path_with_options<P: AsRef<Path> + std::fmt::Debug>(
     Ok(result.to_path_buf())
 }
 
+/// Normalize `path` against the *nearest existing parent*, then lexically resolve whatever is
+/// left.
+///
+/// Walks the path component-by-component (from the canonicalized CWD for relative paths, or from
+/// the root for absolute ones), attempting `fs::canonicalize` on the accumulated prefix at each
+/// step; the last prefix that actually canonicalized becomes the anchor. Everything after that
+/// point is resolved purely lexically (popping for `..`, skipping `.`) with no further filesystem
+/// calls, so a `..` can never pop back into the already symlink-resolved anchor segments.
+fn normalize_path_hybrid(path: &Path) -> std::io::Result<PathBuf> {
+    let mut anchor = if path.is_absolute() {
+        PathBuf::new()
+    } else {
+        fs::canonicalize(".")?
+    };
+    let mut probe = anchor.clone();
+    let mut components = path.components();
+    let mut tail: Vec<Component> = Vec::new();
+    // walk forward while the accumulated prefix still canonicalizes; on the first failure, stop
+    // calling fs::canonicalize (it can never succeed again once a segment is missing) and hand
+    // everything from here on to the purely lexical resolution below
+    for component in components.by_ref() {
+        probe.push(component);
+        match fs::canonicalize(&probe) {
+            Ok(canonicalized) => {
+                anchor = canonicalized;
+                probe = anchor.clone();
+            }
+            Err(_) => {
+                tail.push(component);
+                break;
+            }
+        }
+    }
+    tail.extend(components);
+    // resolve the un-canonicalized tail lexically, onto the anchor, without touching the
+    // filesystem again
+    let mut result = anchor;
+    let mut tail_depth = 0usize;
+    for component in tail {
+        match component {
+            Component::ParentDir => {
+                // never pop past the anchor: a `..` with nothing pending lexically is a no-op
+                if tail_depth > 0 {
+                    result.pop();
+                    tail_depth -= 1;
+                }
+            }
+            Component::CurDir => {}
+            Component::Normal(_) => {
+                result.push(component);
+                tail_depth += 1;
+            }
+            _ => result.push(component),
+        }
+    }
+    Ok(dunce::simplified(&result).to_path_buf())
+}
+
 pub fn normalize_path<P: AsRef<Path> + std::fmt::Debug>(path: P) -> std::io::Result<PathBuf> {
     normalize_path_with_options(path, &NormalizeOptions::default())
 }
+
+/// Normalize `path` lexically, based solely on its text; never touches the filesystem.
+/// * unlike [`normalize_path`], this never calls `fs::canonicalize` and works for paths that
+///   don't exist; a leading `..` with nothing to pop (eg, `../../foo`) is preserved as-is
+pub fn normalize_path_lexical<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match result.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    result.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => result.push(component),
+            },
+            _ => result.push(component),
+        }
+    }
+    result
+}
+
+/// A trusted base directory that relative fragments can be joined onto, staying normalized.
+/// * unlike `Path::join`, this never collapses into a verbatim (`\\?\`) Windows path
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BasePath<'p> {
+    base: &'p Path,
+}
+
+impl<'p> BasePath<'p> {
+    pub fn new(base: &'p Path) -> Self {
+        Self { base }
+    }
+
+    pub fn as_path(&self) -> &Path {
+        self.base
+    }
+
+    /// Join `fragment` onto this base, normalizing (and dunce-simplifying) the result.
+    pub fn join<P: AsRef<Path>>(&self, fragment: P) -> std::io::Result<BasePathBuf> {
+        let joined = self.base.join(fragment.as_ref());
+        Ok(BasePathBuf {
+            base: normalize_path(joined)?,
+        })
+    }
+
+    /// The parent of this base, as given by [`Path::parent`] on the stored base path.
+    /// * the result is not re-normalized: it is only as normalized as the base passed to
+    ///   [`BasePath::new`] (eg, a base ending in `..` yields its lexical parent, and a
+    ///   single-component relative base yields an empty path)
+    pub fn parent(&self) -> Option<BasePath<'p>> {
+        self.base.parent().map(BasePath::new)
+    }
+}
+
+/// An owned, normalized path produced from a [`BasePath`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BasePathBuf {
+    base: PathBuf,
+}
+
+// hand-written so deserializing can't bypass `normalize_path` and reintroduce the `..`
+// traversal / verbatim-prefix / reserved-name issues this type exists to prevent
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BasePathBuf {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(serde::Deserialize)]
+        struct Repr {
+            base: PathBuf,
+        }
+        let repr = Repr::deserialize(deserializer)?;
+        let base = normalize_path(repr.base).map_err(serde::de::Error::custom)?;
+        Ok(Self { base })
+    }
+}
+
+impl BasePathBuf {
+    pub fn as_path(&self) -> &Path {
+        &self.base
+    }
+
+    pub fn into_path_buf(self) -> PathBuf {
+        self.base
+    }
+
+    /// Push `fragment` onto this path, re-normalizing (and dunce-simplifying) the result in
+    /// place.
+    pub fn push<P: AsRef<Path>>(&mut self, fragment: P) -> std::io::Result<()> {
+        let joined = self.base.join(fragment.as_ref());
+        self.base = normalize_path(joined)?;
+        Ok(())
+    }
+
+    /// Remove the last component, mirroring [`PathBuf::pop`].
+    pub fn pop(&mut self) -> bool {
+        self.base.pop()
+    }
+}
+
+impl AsRef<Path> for BasePathBuf {
+    fn as_ref(&self) -> &Path {
+        &self.base
+    }
+}
+
+/// Return the OS-appropriate *display* name of `path`'s final component, without altering the
+/// stored path.
+/// * on Windows this recovers the true on-disk casing of the last component via a directory
+///   read; on other platforms it's a cheap passthrough of `path.file_name()`
+pub fn localized_display<P: AsRef<Path>>(path: P) -> std::ffi::OsString {
+    let path = path.as_ref();
+    #[cfg(windows)]
+    {
+        if let Some(name) = localize_name(path) {
+            return name;
+        }
+    }
+    path.file_name()
+        .map(OsStr::to_os_string)
+        .unwrap_or_else(|| path.as_os_str().to_os_string())
+}
+
+#[cfg(windows)]
+fn localize_name(path: &Path) -> Option<std::ffi::OsString> {
+    let file_name = path.file_name()?;
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty())?;
+    let target = file_name.to_string_lossy().to_ascii_uppercase();
+    fs::read_dir(parent).ok()?.find_map(|entry| {
+        let entry = entry.ok()?;
+        let candidate = entry.file_name();
+        if candidate.to_string_lossy().to_ascii_uppercase() == target {
+            Some(candidate)
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // unique-per-test scratch dir under the system temp dir, cleaned up on drop
+    struct ScratchDir {
+        path: PathBuf,
+    }
+
+    impl ScratchDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "path_normalize-test-{name}-{}",
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            Self { path }
+        }
+    }
+
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+
+    #[test]
+    fn hybrid_resolves_dangling_parent_dir_past_an_existing_parent() {
+        let scratch = ScratchDir::new("hybrid-dangling");
+        fs::create_dir_all(scratch.path.join("real")).unwrap();
+        let path = scratch.path.join("real/missing/../missing2");
+
+        let result = normalize_path_hybrid(&path).unwrap();
+
+        let anchor = fs::canonicalize(&scratch.path).unwrap();
+        assert_eq!(result, dunce::simplified(&anchor.join("real/missing2")).to_path_buf());
+    }
+
+    #[test]
+    fn hybrid_never_pops_past_the_canonical_anchor() {
+        let scratch = ScratchDir::new("hybrid-anchor-clamp");
+        fs::create_dir_all(scratch.path.join("real")).unwrap();
+        // the dangling `missing` component forces the anchor to stop at `real`; the extra `..`s
+        // beyond what's pending lexically must not pop back out of that anchor
+        let path = scratch.path.join("real/missing/../../../outside");
+
+        let result = normalize_path_hybrid(&path).unwrap();
+
+        let anchor = fs::canonicalize(scratch.path.join("real")).unwrap();
+        assert_eq!(result, dunce::simplified(&anchor.join("outside")).to_path_buf());
+    }
+
+    #[test]
+    fn nonexistent_relative_path_degrades_to_lexical_from_cwd() {
+        let name = format!("path_normalize-test-nonexistent-{}", std::process::id());
+        let path = Path::new(&name).join("missing/nested");
+
+        let result = normalize_path_with_options(&path, &NormalizeOptions::default()).unwrap();
+
+        let cwd = fs::canonicalize(".").unwrap();
+        assert_eq!(result, dunce::simplified(&cwd.join(&path)).to_path_buf());
+    }
+
+    #[test]
+    fn lexical_preserves_leading_parent_dirs() {
+        assert_eq!(
+            normalize_path_lexical(Path::new("../../foo")),
+            Path::new("../../foo")
+        );
+    }
+
+    #[test]
+    fn lexical_pops_into_a_leading_parent_dir_once_exhausted() {
+        assert_eq!(
+            normalize_path_lexical(Path::new("a/../../foo")),
+            Path::new("../foo")
+        );
+    }
+
+    #[test]
+    fn lexical_clamps_parent_dir_at_root() {
+        assert_eq!(normalize_path_lexical(Path::new("/../foo")), Path::new("/foo"));
+    }
+
+    #[test]
+    fn expand_tilde_expands_standalone_leading_tilde() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_tilde(Path::new("~/foo")), home.join("foo"));
+    }
+
+    #[test]
+    fn expand_tilde_leaves_embedded_username_unexpanded() {
+        // `~user/foo` is a single `~user` component, not a standalone `~`, so it's untouched
+        assert_eq!(expand_tilde(Path::new("~user/foo")), Path::new("~user/foo"));
+    }
+
+    #[test]
+    fn fold_home_dir_rewrites_path_under_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(fold_home_dir(home.join("foo")), Path::new("~/foo"));
+    }
+
+    #[test]
+    fn base_path_join_normalizes_onto_the_base() {
+        let scratch = ScratchDir::new("base-path-join");
+        fs::create_dir_all(scratch.path.join("sub")).unwrap();
+
+        let base = BasePath::new(&scratch.path);
+        let joined = base.join("sub").unwrap();
+
+        assert_eq!(joined.as_path(), fs::canonicalize(scratch.path.join("sub")).unwrap());
+    }
+
+    #[test]
+    fn base_path_parent_stays_anchored() {
+        let scratch = ScratchDir::new("base-path-parent");
+        fs::create_dir_all(scratch.path.join("sub")).unwrap();
+        let sub = scratch.path.join("sub");
+
+        let base = BasePath::new(&sub);
+        let parent = base.parent().unwrap();
+
+        assert_eq!(parent.as_path(), scratch.path.as_path());
+    }
+
+    #[test]
+    fn base_path_buf_push_and_pop_stay_normalized() {
+        let scratch = ScratchDir::new("base-path-buf");
+        fs::create_dir_all(scratch.path.join("sub/nested")).unwrap();
+
+        let mut buf = BasePath::new(&scratch.path).join("sub").unwrap();
+        assert_eq!(buf.as_path(), fs::canonicalize(scratch.path.join("sub")).unwrap());
+
+        buf.push("nested").unwrap();
+        assert_eq!(buf.as_path(), fs::canonicalize(scratch.path.join("sub/nested")).unwrap());
+
+        assert!(buf.pop());
+        assert_eq!(buf.as_path(), fs::canonicalize(scratch.path.join("sub")).unwrap());
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn localized_display_passes_through_file_name() {
+        assert_eq!(localized_display(Path::new("/foo/Bar.txt")), OsStr::new("Bar.txt"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn normalize_mode_round_trips_as_a_lowercase_string() {
+        for (mode, tag) in [
+            (NormalizeMode::Strict, "\"strict\""),
+            (NormalizeMode::Hybrid, "\"hybrid\""),
+            (NormalizeMode::Lexical, "\"lexical\""),
+        ] {
+            assert_eq!(serde_json::to_string(&mode).unwrap(), tag);
+            assert_eq!(serde_json::from_str::<NormalizeMode>(tag).unwrap(), mode);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn normalize_options_deserializes_missing_fields_to_defaults() {
+        let options: NormalizeOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(options, NormalizeOptions::default());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn base_path_buf_deserialize_routes_through_normalize_path() {
+        let json = r#"{"base":"some/relative/../path"}"#;
+
+        let buf: BasePathBuf = serde_json::from_str(json).unwrap();
+
+        assert_eq!(buf.as_path(), normalize_path("some/relative/../path").unwrap());
+    }
+}